@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::{AccelData, GyroData};
+
+const BIAS_FILE_NAME: &str = "gyro_bias.txt";
+// Thresholds borrowed from JoyShockLibrary's GamepadMotion continuous calibration:
+// "at rest" requires near-1g accel and near-zero gyro, sustained for a short window.
+const REST_ACCEL_TOLERANCE_G: f64 = 0.05;
+const REST_GYRO_THRESHOLD_DPS: f64 = 2.0;
+const REST_SUSTAIN_TICKS: u32 = 200;
+const BIAS_LEARNING_RATE: f64 = 0.01;
+const FORCED_LEARNING_RATE: f64 = 0.1;
+const FORCED_CALIBRATION_SECS: f64 = 3.0;
+const STANDARD_GRAVITY_MPS2: f64 = 9.80665;
+const SAVE_EVERY_N_UPDATES: u32 = 500;
+
+/// Maintains a running gyro zero-offset and subtracts it from raw samples.
+///
+/// While the device is detected at rest the bias is nudged towards the raw
+/// reading with a weighted moving average; motion pauses accumulation so the
+/// bias doesn't chase a turning controller.
+pub struct GyroCalibrator {
+    bias: GyroData,
+    auto_calibrate: bool,
+    rest_streak: u32,
+    forced_until: Option<Instant>,
+    bias_path: PathBuf,
+    updates_since_save: u32,
+}
+
+impl GyroCalibrator {
+    pub fn new(force_calibrate: bool, auto_calibrate: bool) -> Self {
+        let bias_path = bias_file_path();
+        let bias = if force_calibrate {
+            GyroData::default()
+        } else {
+            load_bias(&bias_path).unwrap_or_default()
+        };
+        let forced_until = force_calibrate
+            .then(|| Instant::now() + Duration::from_secs_f64(FORCED_CALIBRATION_SECS));
+        Self {
+            bias,
+            auto_calibrate,
+            rest_streak: 0,
+            forced_until,
+            bias_path,
+            updates_since_save: 0,
+        }
+    }
+
+    /// Returns `raw` with the current bias subtracted, updating the bias
+    /// estimate first if the controller currently appears to be at rest.
+    pub fn apply(&mut self, raw: GyroData, accel: AccelData) -> GyroData {
+        let forced = self.forced_until.is_some_and(|until| Instant::now() < until);
+        if !forced && self.forced_until.take().is_some() {
+            // the forced calibration window just elapsed; persist what we learned
+            self.save();
+        }
+
+        let bias_corrected = GyroData {
+            x: raw.x - self.bias.x,
+            y: raw.y - self.bias.y,
+            z: raw.z - self.bias.z,
+        };
+
+        // A forced `--calibrate` run is the user asserting the controller is
+        // still, so it accumulates unconditionally instead of waiting for
+        // the (bias-corrected) gyro to settle under the anti-motion
+        // threshold — a controller with enough resting drift to need this
+        // feature would otherwise never pass that check and the window
+        // would elapse with the bias still at zero.
+        if forced {
+            self.bias = self.bias * (1.0 - FORCED_LEARNING_RATE) + raw * FORCED_LEARNING_RATE;
+        } else if self.auto_calibrate {
+            if is_at_rest(bias_corrected, accel) {
+                self.rest_streak += 1;
+                if self.rest_streak >= REST_SUSTAIN_TICKS {
+                    self.bias = self.bias * (1.0 - BIAS_LEARNING_RATE) + raw * BIAS_LEARNING_RATE;
+                    self.updates_since_save += 1;
+                    if self.updates_since_save >= SAVE_EVERY_N_UPDATES {
+                        self.save();
+                        self.updates_since_save = 0;
+                    }
+                }
+            } else {
+                self.rest_streak = 0;
+            }
+        }
+
+        GyroData {
+            x: raw.x - self.bias.x,
+            y: raw.y - self.bias.y,
+            z: raw.z - self.bias.z,
+        }
+    }
+
+    fn save(&self) {
+        let _ = fs::write(
+            &self.bias_path,
+            format!("{} {} {}", self.bias.x, self.bias.y, self.bias.z),
+        );
+    }
+}
+
+fn is_at_rest(gyro: GyroData, accel: AccelData) -> bool {
+    let gyro_mag = (gyro.x * gyro.x + gyro.y * gyro.y + gyro.z * gyro.z).sqrt();
+    let accel_mag_g =
+        (accel.x * accel.x + accel.y * accel.y + accel.z * accel.z).sqrt() / STANDARD_GRAVITY_MPS2;
+    gyro_mag < REST_GYRO_THRESHOLD_DPS && (accel_mag_g - 1.0).abs() < REST_ACCEL_TOLERANCE_G
+}
+
+fn bias_file_path() -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(BIAS_FILE_NAME)
+}
+
+fn load_bias(path: &Path) -> Option<GyroData> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut parts = contents.split_whitespace();
+    Some(GyroData {
+        x: parts.next()?.parse().ok()?,
+        y: parts.next()?.parse().ok()?,
+        z: parts.next()?.parse().ok()?,
+    })
+}
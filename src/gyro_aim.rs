@@ -0,0 +1,152 @@
+use std::f64::consts::PI;
+
+use crate::GyroData;
+
+// Stick-units accumulated per deg/s of yaw/pitch, per tick. Small because the
+// physical tick rate is ~800Hz; a sustained 100deg/s turn should reach full
+// deflection in well under a second, not instantly.
+const GYRO_SENSITIVITY: f64 = 0.0015;
+const GYRO_ACTIVATION_THRESHOLD_DPS: f64 = 1.0;
+// Decays the accumulated aim vector back towards center every tick so gyro
+// aiming behaves like a spring-loaded stick rather than an absolute position.
+const AIM_DECAY: f64 = 0.9;
+
+const FLICK_ACTIVATION_RADIUS: f64 = 0.9;
+// Stick-units of yaw output per radian of flick-stick angle/rotation.
+const FLICK_BURST_SCALE: f64 = 0.6;
+// Spreads the initial flick burst over ~100ms (at the ~800Hz report rate)
+// instead of a single report, since a game reading the stick as a turn
+// *rate* needs the deflection held for a while to actually turn by the
+// flicked angle, not a single near-instant report it may miss entirely.
+const FLICK_BURST_DURATION_TICKS: u32 = 80;
+
+fn to_i16_stick(value: f64) -> i16 {
+    (value.clamp(-1.0, 1.0) * 32767.0).round() as i16
+}
+
+/// Converts stick deflection past `FLICK_ACTIVATION_RADIUS` into yaw: an
+/// initial burst proportional to the stick's angle when it first crosses the
+/// threshold (a "flick" to face that heading), held for
+/// `FLICK_BURST_DURATION_TICKS` so the game actually turns by that angle
+/// instead of missing a single near-instant report, then incremental yaw
+/// for as long as the stick keeps rotating while held out.
+struct FlickStick {
+    active: bool,
+    last_angle: f64,
+    burst_per_tick: f64,
+    burst_ticks_remaining: u32,
+}
+
+impl FlickStick {
+    fn new() -> Self {
+        Self {
+            active: false,
+            last_angle: 0.0,
+            burst_per_tick: 0.0,
+            burst_ticks_remaining: 0,
+        }
+    }
+
+    /// Returns the yaw contribution (in stick-units) for this tick.
+    fn apply(&mut self, rx: i16, ry: i16) -> f64 {
+        let nx = rx as f64 / 32768.0;
+        let ny = ry as f64 / 32768.0;
+        let radius = (nx * nx + ny * ny).sqrt();
+
+        if radius < FLICK_ACTIVATION_RADIUS {
+            self.active = false;
+            // Letting go of the stick doesn't cut an in-progress flick short.
+            if self.burst_ticks_remaining > 0 {
+                self.burst_ticks_remaining -= 1;
+                return self.burst_per_tick;
+            }
+            return 0.0;
+        }
+
+        // Heading measured clockwise from stick-forward, so a push to the
+        // right turns right and a push straight forward doesn't turn at all.
+        let angle = nx.atan2(ny);
+        if !self.active {
+            self.active = true;
+            self.last_angle = angle;
+            self.burst_per_tick =
+                (angle * FLICK_BURST_SCALE) / FLICK_BURST_DURATION_TICKS as f64;
+            self.burst_ticks_remaining = FLICK_BURST_DURATION_TICKS;
+        }
+
+        let mut delta_angle = angle - self.last_angle;
+        if delta_angle > PI {
+            delta_angle -= 2.0 * PI;
+        } else if delta_angle < -PI {
+            delta_angle += 2.0 * PI;
+        }
+        self.last_angle = angle;
+
+        let burst_contribution = if self.burst_ticks_remaining > 0 {
+            self.burst_ticks_remaining -= 1;
+            self.burst_per_tick
+        } else {
+            0.0
+        };
+
+        // Continued rotation tracks incrementally on top of the ongoing burst.
+        delta_angle * FLICK_BURST_SCALE + burst_contribution
+    }
+}
+
+/// Maps bias-corrected, player-space gyro motion (and optionally flick-stick
+/// gestures on the right stick) onto the right stick's x/y axes, for games
+/// that only read analog stick input and ignore DS4 motion fields entirely.
+pub struct GyroAimStick {
+    gyro_to_stick: bool,
+    flick_stick: Option<FlickStick>,
+    aim_x: f64,
+    aim_y: f64,
+}
+
+impl GyroAimStick {
+    pub fn new(gyro_to_stick: bool, flick_stick: bool) -> Self {
+        Self {
+            gyro_to_stick,
+            flick_stick: flick_stick.then(FlickStick::new),
+            aim_x: 0.0,
+            aim_y: 0.0,
+        }
+    }
+
+    /// Returns the right-stick (x, y) pair to send this tick, in the same
+    /// raw i16 range as `XInputState::right_stick_raw`.
+    pub fn apply(&mut self, gyro: GyroData, physical_rx: i16, physical_ry: i16) -> (i16, i16) {
+        let mut yaw = 0.0;
+        let mut pitch = 0.0;
+
+        if self.gyro_to_stick {
+            if gyro.z.abs() > GYRO_ACTIVATION_THRESHOLD_DPS {
+                yaw += gyro.z * GYRO_SENSITIVITY;
+            }
+            if gyro.x.abs() > GYRO_ACTIVATION_THRESHOLD_DPS {
+                pitch += gyro.x * GYRO_SENSITIVITY;
+            }
+        }
+
+        self.aim_x = (self.aim_x * AIM_DECAY + yaw).clamp(-1.0, 1.0);
+        self.aim_y = (self.aim_y * AIM_DECAY + pitch).clamp(-1.0, 1.0);
+
+        if let Some(flick_stick) = &mut self.flick_stick {
+            // Flick stick reads the physical stick itself as the flick/yaw
+            // gesture, so it fully replaces normal stick output here.
+            let flick_yaw = flick_stick.apply(physical_rx, physical_ry);
+            let out_x = (self.aim_x + flick_yaw).clamp(-1.0, 1.0);
+            return (to_i16_stick(out_x), to_i16_stick(self.aim_y));
+        }
+
+        // Otherwise the physical stick still works normally, blended with
+        // the gyro-aim contribution rather than being discarded.
+        let physical_x = physical_rx as f64 / 32768.0;
+        let physical_y = physical_ry as f64 / 32768.0;
+        let out_x = (self.aim_x + physical_x).clamp(-1.0, 1.0);
+        let out_y = (self.aim_y + physical_y).clamp(-1.0, 1.0);
+
+        (to_i16_stick(out_x), to_i16_stick(out_y))
+    }
+}
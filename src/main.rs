@@ -1,3 +1,9 @@
+mod calibration;
+mod gyro_aim;
+mod orientation;
+mod sticks;
+mod touchpad;
+
 use std::sync::OnceLock;
 use std::time::Duration;
 use windows::Devices::Sensors::{Accelerometer, Gyrometer};
@@ -7,7 +13,19 @@ use std::sync::{Arc, Mutex};
 use tokio::time::interval;
 use xi::XInputState;
 
+use calibration::GyroCalibrator;
+use gyro_aim::GyroAimStick;
+use orientation::PlayerSpaceGyro;
+use touchpad::{TouchpadFromStick, TouchpadFromStickMode};
+
 static ENABLE_DS4_SHARE_BUTTON: OnceLock<bool> = OnceLock::new();
+static FORCE_CALIBRATE: OnceLock<bool> = OnceLock::new();
+static AUTO_CALIBRATE: OnceLock<bool> = OnceLock::new();
+static PLAYER_SPACE_GYRO: OnceLock<bool> = OnceLock::new();
+static CIRCULAR_STICKS: OnceLock<bool> = OnceLock::new();
+static TOUCHPAD_FROM_STICK: OnceLock<Option<TouchpadFromStickMode>> = OnceLock::new();
+static GYRO_TO_STICK: OnceLock<bool> = OnceLock::new();
+static FLICK_STICK: OnceLock<bool> = OnceLock::new();
 
 #[tokio::main]
 async fn main() {
@@ -15,8 +33,40 @@ async fn main() {
         if arg.contains("--enable-share-button") {
             ENABLE_DS4_SHARE_BUTTON.get_or_init(|| true);
         }
+        if arg.contains("--calibrate") {
+            FORCE_CALIBRATE.get_or_init(|| true);
+        }
+        if arg.contains("--no-auto-calibrate") {
+            AUTO_CALIBRATE.get_or_init(|| false);
+        }
+        if arg.contains("--player-space-gyro") {
+            PLAYER_SPACE_GYRO.get_or_init(|| true);
+        }
+        if arg.contains("--circular-sticks") {
+            CIRCULAR_STICKS.get_or_init(|| true);
+        }
+        if let Some(mode) = arg.strip_prefix("--touchpad-from-stick=") {
+            if let Some(mode) = TouchpadFromStickMode::parse(mode) {
+                TOUCHPAD_FROM_STICK.get_or_init(|| Some(mode));
+            } else {
+                eprintln!("Unknown --touchpad-from-stick mode: {mode}");
+            }
+        }
+        if arg.contains("--gyro-to-stick") {
+            GYRO_TO_STICK.get_or_init(|| true);
+        }
+        if arg.contains("--flick-stick") {
+            FLICK_STICK.get_or_init(|| true);
+        }
     }
     ENABLE_DS4_SHARE_BUTTON.get_or_init(|| false);
+    FORCE_CALIBRATE.get_or_init(|| false);
+    AUTO_CALIBRATE.get_or_init(|| true);
+    PLAYER_SPACE_GYRO.get_or_init(|| false);
+    CIRCULAR_STICKS.get_or_init(|| false);
+    TOUCHPAD_FROM_STICK.get_or_init(|| None);
+    GYRO_TO_STICK.get_or_init(|| false);
+    FLICK_STICK.get_or_init(|| false);
 
     let Ok(vigem_driver_client) = vigem_client::Client::connect() else {
         eprintln!("Failed to connect to ViGEm Bus");
@@ -79,10 +129,16 @@ async fn main() {
     });
 
     let gyro_mutex_clone = Arc::clone(&gyro_mutex);
+    let accel_mutex_for_calibration = Arc::clone(&accel_mutex);
     tokio::spawn(async move {
         let inertia = 10.0;
         let mut previous_non_error_reading = GyroData::default();
         let mut broken_time = 0.0;
+        let mut calibrator = GyroCalibrator::new(
+            *FORCE_CALIBRATE.get().unwrap_or(&false),
+            *AUTO_CALIBRATE.get().unwrap_or(&true),
+        );
+        let mut player_space_filter = PlayerSpaceGyro::new();
         loop {
             let mut og_gyro_data = read_gyro(&gyro).unwrap_or_default();
             og_gyro_data = legion_go_gyro_axis_swap(og_gyro_data);
@@ -103,9 +159,19 @@ async fn main() {
                 previous_non_error_reading = og_gyro_data;
                 og_gyro_data
             };
+            let accel_snapshot = {
+                let locked_accel = accel_mutex_for_calibration.lock().unwrap();
+                *locked_accel
+            };
+            let calibrated_gyro_data = calibrator.apply(new_gyro_data, accel_snapshot);
+            let output_gyro_data = if *PLAYER_SPACE_GYRO.get().unwrap_or(&false) {
+                player_space_filter.apply(calibrated_gyro_data, accel_snapshot)
+            } else {
+                calibrated_gyro_data
+            };
             {
                 let mut locked_gyro = gyro_mutex_clone.lock().unwrap();
-                *locked_gyro = new_gyro_data;
+                *locked_gyro = output_gyro_data;
             }
         }
     });
@@ -135,7 +201,17 @@ async fn main() {
     println!("Service started");
 
     let mut timestamp: u16 = 0;
+    let mut last_report_at = std::time::Instant::now();
     let mut interval = interval(Duration::from_micros(1250));
+    let mut touchpad_from_stick = TOUCHPAD_FROM_STICK
+        .get()
+        .cloned()
+        .flatten()
+        .map(TouchpadFromStick::new);
+    let gyro_to_stick = *GYRO_TO_STICK.get().unwrap_or(&false);
+    let flick_stick = *FLICK_STICK.get().unwrap_or(&false);
+    let mut gyro_aim_stick =
+        (gyro_to_stick || flick_stick).then(|| GyroAimStick::new(gyro_to_stick, flick_stick));
 
     loop {
         interval.tick().await;
@@ -153,7 +229,13 @@ async fn main() {
             *locked_accel
         };
 
-        let report = put_xinput_state_into_builder(xstate, DS4ReportExBuilder::new())
+        let report = put_xinput_state_into_builder(
+            xstate,
+            DS4ReportExBuilder::new(),
+            touchpad_from_stick.as_mut(),
+            gyro_aim_stick.as_mut(),
+            gyro_data,
+        )
             .gyro_x(convert_umdf_gyro_to_dualshock_x(gyro_data.x))
             .gyro_y(convert_umdf_gyro_to_dualshock_y(gyro_data.y))
             .gyro_z(convert_umdf_gyro_to_dualshock_z(gyro_data.z))
@@ -166,11 +248,31 @@ async fn main() {
 
         let _ = ds4wired.update_ex(&report);
 
-        timestamp = timestamp.wrapping_add(188);
+        let now = std::time::Instant::now();
+        timestamp = timestamp.wrapping_add(ds4_timestamp_ticks_elapsed(now - last_report_at));
+        last_report_at = now;
     }
 }
 
-fn put_xinput_state_into_builder(xstate: Option<XInputState>, ds4reb: DS4ReportExBuilder) -> DS4ReportExBuilder {
+/// The DS4 report's motion timestamp field increments in units of 5.33us
+/// (i.e. at 188 ticks/ms), regardless of how long the previous report
+/// actually took to send; convert real elapsed time into that tick unit so
+/// the value stays accurate even when the send interval slips.
+const DS4_TIMESTAMP_TICK_SECS: f64 = 5.33e-6;
+
+fn ds4_timestamp_ticks_elapsed(elapsed: Duration) -> u16 {
+    (elapsed.as_secs_f64() / DS4_TIMESTAMP_TICK_SECS)
+        .round()
+        .clamp(0.0, u16::MAX as f64) as u16
+}
+
+fn put_xinput_state_into_builder(
+    xstate: Option<XInputState>,
+    ds4reb: DS4ReportExBuilder,
+    touch_from_stick: Option<&mut touchpad::TouchpadFromStick>,
+    gyro_aim_stick: Option<&mut GyroAimStick>,
+    gyro_data: GyroData,
+) -> DS4ReportExBuilder {
     if let Some(xstate) = xstate {
         let buttons = DS4Buttons::new()
             .triangle(xstate.north_button())
@@ -198,7 +300,27 @@ fn put_xinput_state_into_builder(xstate: Option<XInputState>, ds4reb: DS4ReportE
 
         let (lx, ly) = xstate.left_stick_raw();
         let (rx, ry) = xstate.right_stick_raw();
-        ds4reb
+        let (lx, ly) = if *CIRCULAR_STICKS.get().unwrap_or(&false) {
+            sticks::remap_square_to_circle(lx, ly)
+        } else {
+            (lx, ly)
+        };
+        let (rx, ry) = if *CIRCULAR_STICKS.get().unwrap_or(&false) {
+            sticks::remap_square_to_circle(rx, ry)
+        } else {
+            (rx, ry)
+        };
+
+        // Touchpad-from-stick reads the physical right stick, independent of
+        // any gyro-aim/flick-stick override of thumb_rx/thumb_ry below.
+        let touch = touch_from_stick.map(|touch_from_stick| touch_from_stick.apply(rx, ry));
+
+        let (rx, ry) = if let Some(gyro_aim_stick) = gyro_aim_stick {
+            gyro_aim_stick.apply(gyro_data, rx, ry)
+        } else {
+            (rx, ry)
+        };
+        let ds4reb = ds4reb
             .buttons(buttons)
             .special(special_buttons)
             .thumb_lx(normalize_i16_to_u8(lx, false))
@@ -206,7 +328,17 @@ fn put_xinput_state_into_builder(xstate: Option<XInputState>, ds4reb: DS4ReportE
             .thumb_rx(normalize_i16_to_u8(rx, false))
             .thumb_ry(normalize_i16_to_u8(ry, true))
             .trigger_l(xstate.left_trigger())
-            .trigger_r(xstate.right_trigger())
+            .trigger_r(xstate.right_trigger());
+
+        if let Some(touch) = touch {
+            ds4reb
+                .touch_1_active(touch.active)
+                .touch_1_id(touch.contact_id)
+                .touch_1_x(touch.x)
+                .touch_1_y(touch.y)
+        } else {
+            ds4reb
+        }
     } else {
         ds4reb
     }
@@ -259,37 +391,35 @@ fn convert_umdf_gyro_to_dualshock_z(umdf_value: f64) -> i16 {
     intermediate_value.clamp(I16_MIN, I16_MAX) as i16
 }
 fn convert_umdf_gyro_to_dualshock(umdf_value: f64) -> i16 {
-    // Define the maximum angular velocity representable by the DualShock 4 gyro.
-    const MAX_DPS: f64 = 2000.0;
-    // Define i16 min and max to avoid magic numbers
+    // Real DS4 motion reports use a fixed resolution of ~16 LSB per deg/s, not
+    // a rescaling of the full i16 range; games (e.g. rpcs3) assume this
+    // resolution when converting the raw field back to deg/s.
+    const DS4_GYRO_LSB_PER_DPS: f64 = 16.0;
     const I16_MAX: f64 = 32767.0;
     const I16_MIN: f64 = -32768.0;
 
-    // 1. Clamping
-    let clamped_value = umdf_value.clamp(-MAX_DPS, MAX_DPS);
-
-    // 2. Scaling and 3. Rounding and Type Conversion
-    // Calculate the sensitivity.  We are going from degrees/second to i16.
-    let scale_factor: f64 = I16_MAX / MAX_DPS;
-    let scaled_value = (clamped_value * scale_factor).round();
-
-    //clamp to i16 range, cast to i16 and return.
+    let scaled_value = (umdf_value * DS4_GYRO_LSB_PER_DPS).round();
     scaled_value.clamp(I16_MIN, I16_MAX) as i16
 }
 
 fn convert_umdf_accel_to_dualshock(umdf_value: f64) -> i16 {
-    const SCALE_FACTOR: f64 = 84626.0;
-    let umdf_max: f64 = 9.8;
-    let clamped_value = umdf_value.clamp(-umdf_max, umdf_max);
-    let scaled_value = clamped_value / umdf_max * SCALE_FACTOR;
-    scaled_value.round() as i16
+    // Real DS4 motion reports use a fixed resolution of ~8192 LSB per g;
+    // UMDF accelerometer readings come in m/s^2, so convert to g first.
+    const DS4_ACCEL_LSB_PER_G: f64 = 8192.0;
+    const STANDARD_GRAVITY_MPS2: f64 = 9.80665;
+    const I16_MAX: f64 = 32767.0;
+    const I16_MIN: f64 = -32768.0;
+
+    let value_g = umdf_value / STANDARD_GRAVITY_MPS2;
+    let scaled_value = (value_g * DS4_ACCEL_LSB_PER_G).round();
+    scaled_value.clamp(I16_MIN, I16_MAX) as i16
 }
 
 #[derive(Debug, Clone, Copy, Default)]
-struct GyroData {
-    x: f64,
-    y: f64,
-    z: f64,
+pub(crate) struct GyroData {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) z: f64,
 }
 
 impl std::ops::Add for GyroData {
@@ -312,12 +442,22 @@ impl std::ops::Div<f64> for GyroData {
         }
     }
 }
+impl std::ops::Mul<f64> for GyroData {
+    type Output = Self;
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, Default)]
-struct AccelData {
-    x: f64,
-    y: f64,
-    z: f64,
+pub(crate) struct AccelData {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) z: f64,
 }
 
 fn legion_go_gyro_axis_swap(raw: GyroData) -> GyroData {
@@ -0,0 +1,211 @@
+use std::time::Instant;
+
+use crate::{AccelData, GyroData};
+
+// Complementary-filter gain: how strongly each tick nudges the gyro-integrated
+// orientation towards the accelerometer-derived gravity direction. Small so
+// gyro noise during fast motion doesn't get overridden by a noisy accel sample.
+const COMPLEMENTARY_GAIN: f64 = 0.02;
+const DEG_TO_RAD: f64 = std::f64::consts::PI / 180.0;
+const RAD_TO_DEG: f64 = 180.0 / std::f64::consts::PI;
+
+#[derive(Debug, Clone, Copy)]
+struct Vec3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Vec3 {
+    const UP: Vec3 = Vec3 { x: 0.0, y: 0.0, z: 1.0 };
+
+    fn magnitude(self) -> f64 {
+        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    }
+
+    fn normalize_or(self, fallback: Vec3) -> Vec3 {
+        let mag = self.magnitude();
+        if mag < 1e-9 {
+            fallback
+        } else {
+            Vec3 { x: self.x / mag, y: self.y / mag, z: self.z / mag }
+        }
+    }
+
+    fn dot(self, other: Vec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn cross(self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    fn scale(self, s: f64) -> Vec3 {
+        Vec3 { x: self.x * s, y: self.y * s, z: self.z * s }
+    }
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        Vec3 { x: self.x - other.x, y: self.y - other.y, z: self.z - other.z }
+    }
+}
+
+/// Unit quaternion (w, x, y, z) used to track the controller's orientation
+/// relative to its starting pose.
+#[derive(Debug, Clone, Copy)]
+struct Quaternion {
+    w: f64,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::IDENTITY
+    }
+}
+
+impl Quaternion {
+    const IDENTITY: Quaternion = Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 };
+
+    fn from_axis_angle(axis: Vec3, angle_rad: f64) -> Quaternion {
+        if angle_rad.abs() < 1e-12 {
+            return Quaternion::IDENTITY;
+        }
+        let half = angle_rad * 0.5;
+        let s = half.sin();
+        Quaternion { w: half.cos(), x: axis.x * s, y: axis.y * s, z: axis.z * s }
+    }
+
+    /// Shortest rotation that takes unit vector `from` onto unit vector `to`.
+    fn rotation_between(from: Vec3, to: Vec3) -> Quaternion {
+        let cos_angle = from.dot(to).clamp(-1.0, 1.0);
+        let axis = from.cross(to);
+        let axis_mag = axis.magnitude();
+        if axis_mag < 1e-9 {
+            // Parallel (or anti-parallel) vectors have no well-defined rotation
+            // axis; treat both cases as "already aligned" rather than spin on
+            // an arbitrary axis.
+            return Quaternion::IDENTITY;
+        }
+        let angle = cos_angle.acos();
+        Quaternion::from_axis_angle(axis.scale(1.0 / axis_mag), angle)
+    }
+
+    fn mul(self, rhs: Quaternion) -> Quaternion {
+        Quaternion {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
+    }
+
+    fn normalize(self) -> Quaternion {
+        let mag = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        if mag < 1e-12 {
+            return Quaternion::IDENTITY;
+        }
+        Quaternion { w: self.w / mag, x: self.x / mag, y: self.y / mag, z: self.z / mag }
+    }
+
+    fn rotate_vector(self, v: Vec3) -> Vec3 {
+        // v' = q * v * q_conjugate, using the quaternion-vector form directly.
+        let qv = Vec3 { x: self.x, y: self.y, z: self.z };
+        let uv = qv.cross(v);
+        let uuv = qv.cross(uv);
+        Vec3 {
+            x: v.x + (uv.x * self.w + uuv.x) * 2.0,
+            y: v.y + (uv.y * self.w + uuv.y) * 2.0,
+            z: v.z + (uv.z * self.w + uuv.z) * 2.0,
+        }
+    }
+
+    fn slerp(self, other: Quaternion, t: f64) -> Quaternion {
+        let mut dot = self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z;
+        let mut other = other;
+        if dot < 0.0 {
+            // take the short path
+            other = Quaternion { w: -other.w, x: -other.x, y: -other.y, z: -other.z };
+            dot = -dot;
+        }
+        if dot > 0.9995 {
+            // nearly identical; linear interpolation avoids a division by ~0
+            return Quaternion {
+                w: self.w + (other.w - self.w) * t,
+                x: self.x + (other.x - self.x) * t,
+                y: self.y + (other.y - self.y) * t,
+                z: self.z + (other.z - self.z) * t,
+            }
+            .normalize();
+        }
+        let theta_0 = dot.acos();
+        let theta = theta_0 * t;
+        let sin_theta_0 = theta_0.sin();
+        let s0 = (theta_0 - theta).sin() / sin_theta_0;
+        let s1 = theta.sin() / sin_theta_0;
+        Quaternion {
+            w: self.w * s0 + other.w * s1,
+            x: self.x * s0 + other.x * s1,
+            y: self.y * s0 + other.y * s1,
+            z: self.z * s0 + other.z * s1,
+        }
+    }
+}
+
+/// Fuses gyro and accelerometer samples into a drift-corrected orientation
+/// (mirroring yuzu's `UpdateOrientation`) and uses it to rotate raw gyro
+/// readings into a "player space" frame, so handheld tilt doesn't bleed
+/// into the turn axis before conversion to DS4 motion units.
+#[derive(Default)]
+pub struct PlayerSpaceGyro {
+    orientation: Quaternion,
+    last_tick: Option<Instant>,
+}
+
+impl PlayerSpaceGyro {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, gyro: GyroData, accel: AccelData) -> GyroData {
+        let now = Instant::now();
+        let dt = self
+            .last_tick
+            .map(|prev| (now - prev).as_secs_f64())
+            .unwrap_or(0.0);
+        self.last_tick = Some(now);
+
+        let gyro_rad = Vec3 { x: gyro.x * DEG_TO_RAD, y: gyro.y * DEG_TO_RAD, z: gyro.z * DEG_TO_RAD };
+        let angle = gyro_rad.magnitude() * dt;
+        if angle > 0.0 {
+            let axis = gyro_rad.normalize_or(Vec3::UP);
+            let delta = Quaternion::from_axis_angle(axis, angle);
+            self.orientation = self.orientation.mul(delta).normalize();
+        }
+
+        let measured_up = Vec3 { x: accel.x, y: accel.y, z: accel.z }.normalize_or(Vec3::UP);
+        let estimated_up = self.orientation.rotate_vector(Vec3::UP);
+        let correction = Quaternion::rotation_between(estimated_up, measured_up);
+        let accel_corrected = correction.mul(self.orientation).normalize();
+        self.orientation = self.orientation.slerp(accel_corrected, COMPLEMENTARY_GAIN).normalize();
+
+        // Project yaw onto the *fused* world-up axis, not the raw accel
+        // reading, so linear acceleration (not just gravity) contaminating a
+        // single accel sample doesn't leak into the stable player-space axis
+        // the whole quaternion/slerp filter exists to produce.
+        let fused_up = self.orientation.rotate_vector(Vec3::UP);
+        let yaw_rate = gyro_rad.dot(fused_up);
+        let residual = gyro_rad.sub(fused_up.scale(yaw_rate));
+
+        GyroData {
+            x: residual.x * RAD_TO_DEG,
+            y: residual.y * RAD_TO_DEG,
+            z: yaw_rate * RAD_TO_DEG,
+        }
+    }
+}
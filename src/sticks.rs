@@ -0,0 +1,29 @@
+// XInput thumbsticks report a roughly square gate (full-scale X and Y
+// achievable simultaneously in the corners), while a real DS4 stick traces a
+// circular gate. Without remapping, diagonal input get clipped to the DS4's
+// circle well before reaching the corner values the stick actually reports.
+const RADIAL_DEADZONE: f64 = 0.05;
+
+/// Rescales a raw XInput stick axis pair from its square gate onto a unit
+/// circle, preserving angle and leaving axis-aligned input unchanged. Input
+/// within `RADIAL_DEADZONE` of center, axis-aligned or not, becomes (0, 0).
+pub fn remap_square_to_circle(x: i16, y: i16) -> (i16, i16) {
+    let nx = x as f64 / 32768.0;
+    let ny = y as f64 / 32768.0;
+    let radius = (nx * nx + ny * ny).sqrt();
+
+    if radius < RADIAL_DEADZONE {
+        return (0, 0);
+    }
+
+    let angle = ny.atan2(nx);
+    // max(|cos|, |sin|) is the reciprocal of the square gate's edge distance
+    // along this angle (that edge sits at radius 1/max(|cos|,|sin|)), so
+    // multiplying by it brings the square's edge onto the unit circle.
+    let square_max = angle.cos().abs().max(angle.sin().abs()).max(1e-9);
+    let circle_radius = radius * square_max;
+
+    let remapped_x = (circle_radius * angle.cos() * 32768.0).clamp(-32768.0, 32767.0);
+    let remapped_y = (circle_radius * angle.sin() * 32768.0).clamp(-32768.0, 32767.0);
+    (remapped_x.round() as i16, remapped_y.round() as i16)
+}
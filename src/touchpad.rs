@@ -0,0 +1,91 @@
+// Real DS4 touchpads report a 1920x942 contact area.
+const TOUCHPAD_WIDTH: f64 = 1920.0;
+const TOUCHPAD_HEIGHT: f64 = 942.0;
+const STICK_DEADZONE: f64 = 0.15;
+// deg/s-style sensitivity for relative mode: pixels moved per tick at full deflection.
+const RELATIVE_SENSITIVITY: f64 = 12.0;
+// The DS4 contact ID is a 7-bit counter that advances each time a finger lands.
+const CONTACT_ID_MASK: u8 = 0x7f;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchpadFromStickMode {
+    Absolute,
+    Relative,
+}
+
+impl TouchpadFromStickMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "absolute" => Some(Self::Absolute),
+            "relative" => Some(Self::Relative),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TouchOutput {
+    pub active: bool,
+    pub contact_id: u8,
+    pub x: u16,
+    pub y: u16,
+}
+
+/// Drives a single virtual touch contact from the right stick, either as an
+/// absolute position (stick deflection maps linearly into touchpad pixels)
+/// or as a relative, trackball-style accumulator.
+pub struct TouchpadFromStick {
+    mode: TouchpadFromStickMode,
+    cursor_x: f64,
+    cursor_y: f64,
+    contact_id: u8,
+    was_active: bool,
+}
+
+impl TouchpadFromStick {
+    pub fn new(mode: TouchpadFromStickMode) -> Self {
+        Self {
+            mode,
+            cursor_x: TOUCHPAD_WIDTH / 2.0,
+            cursor_y: TOUCHPAD_HEIGHT / 2.0,
+            contact_id: 0,
+            was_active: false,
+        }
+    }
+
+    pub fn apply(&mut self, rx: i16, ry: i16) -> TouchOutput {
+        let nx = rx as f64 / 32768.0;
+        let ny = -(ry as f64) / 32768.0;
+        let deflection = (nx * nx + ny * ny).sqrt();
+        let active = deflection >= STICK_DEADZONE;
+
+        match self.mode {
+            TouchpadFromStickMode::Absolute => {
+                if active {
+                    self.cursor_x = (nx + 1.0) / 2.0 * TOUCHPAD_WIDTH;
+                    self.cursor_y = (ny + 1.0) / 2.0 * TOUCHPAD_HEIGHT;
+                }
+            }
+            TouchpadFromStickMode::Relative => {
+                if active {
+                    self.cursor_x += nx * RELATIVE_SENSITIVITY;
+                    self.cursor_y += ny * RELATIVE_SENSITIVITY;
+                    self.cursor_x = self.cursor_x.clamp(0.0, TOUCHPAD_WIDTH - 1.0);
+                    self.cursor_y = self.cursor_y.clamp(0.0, TOUCHPAD_HEIGHT - 1.0);
+                }
+            }
+        }
+
+        if active && !self.was_active {
+            self.contact_id = self.contact_id.wrapping_add(1) & CONTACT_ID_MASK;
+        }
+        self.was_active = active;
+
+        TouchOutput {
+            active,
+            contact_id: self.contact_id,
+            x: self.cursor_x.round().clamp(0.0, TOUCHPAD_WIDTH - 1.0) as u16,
+            y: self.cursor_y.round().clamp(0.0, TOUCHPAD_HEIGHT - 1.0) as u16,
+        }
+    }
+}